@@ -0,0 +1,216 @@
+//! `git clone` over Git's smart HTTP protocol.
+
+use anyhow::Context;
+use std::fs;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::{packfile, pktline, Kind, Object};
+
+/// A `<sha> <name>` pair from a ref advertisement.
+type RefAd = (String, String);
+
+/// Clones `url` into a new directory `dir`, speaking the smart HTTP protocol
+/// directly: discover refs, fetch a packfile for HEAD, unpack its objects
+/// into `.git/objects`, then check out the resulting tree.
+pub(crate) fn run(url: &str, dir: &str) -> anyhow::Result<()> {
+    let url = url.trim_end_matches('/');
+
+    fs::create_dir_all(dir).context("create clone directory")?;
+    std::env::set_current_dir(dir).context("enter clone directory")?;
+    fs::create_dir(".git").context("create .git")?;
+    fs::create_dir(".git/objects").context("create .git/objects")?;
+    fs::create_dir_all(".git/refs/heads").context("create .git/refs/heads")?;
+
+    let (refs, capabilities) = discover_refs(url)?;
+    let head_sha = refs
+        .iter()
+        .find(|(_, name)| name == "HEAD")
+        .map(|(sha, _)| sha.clone())
+        .context("remote did not advertise a HEAD")?;
+
+    let pack = fetch_pack(url, &head_sha)?;
+    for (kind, content) in packfile::unpack(&pack)? {
+        Object::write(kind, &content)?;
+    }
+
+    for (sha, name) in &refs {
+        if let Some(branch) = name.strip_prefix("refs/heads/") {
+            fs::write(format!(".git/refs/heads/{}", branch), format!("{}\n", sha))
+                .context("write ref")?;
+        }
+    }
+    let head_branch = default_branch(&capabilities).unwrap_or_else(|| "main".to_string());
+    fs::write(".git/HEAD", format!("ref: refs/heads/{}\n", head_branch)).context("write HEAD")?;
+
+    checkout(&head_sha, Path::new("."))?;
+
+    Ok(())
+}
+
+/// `GET $url/info/refs?service=git-upload-pack`: returns the advertised refs
+/// (each `<sha> <name>`, with capabilities after a NUL on the first line)
+/// along with those capabilities.
+fn discover_refs(url: &str) -> anyhow::Result<(Vec<RefAd>, Vec<String>)> {
+    let response = ureq::get(&format!("{url}/info/refs?service=git-upload-pack"))
+        .call()
+        .context("GET info/refs")?;
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut body)
+        .context("read info/refs response")?;
+
+    let mut reader = pktline::Reader::new(&body[..]);
+    let service = reader
+        .read_packet()
+        .context("read service announcement")?
+        .context("missing service announcement")?;
+    anyhow::ensure!(
+        service.starts_with(b"# service=git-upload-pack"),
+        "unexpected service announcement: {:?}",
+        String::from_utf8_lossy(&service)
+    );
+    reader.read_packet().context("read announcement flush")?;
+
+    let mut refs = Vec::new();
+    let mut capabilities = Vec::new();
+    let mut first = true;
+    while let Some(line) = reader.read_packet().context("read ref advertisement")? {
+        let line = line.strip_suffix(b"\n").unwrap_or(&line);
+        let line = if first {
+            first = false;
+            if let Some(nul) = line.iter().position(|&b| b == 0) {
+                capabilities = String::from_utf8_lossy(&line[nul + 1..])
+                    .split(' ')
+                    .map(String::from)
+                    .collect();
+                &line[..nul]
+            } else {
+                line
+            }
+        } else {
+            line
+        };
+        let line = std::str::from_utf8(line).context("ref advertisement isn't UTF-8")?;
+        let (sha, name) = line.split_once(' ').context("ref line missing a space")?;
+        refs.push((sha.to_string(), name.to_string()));
+    }
+    Ok((refs, capabilities))
+}
+
+/// Extracts the default branch name from a `symref=HEAD:refs/heads/<name>`
+/// capability, if the server advertised one.
+fn default_branch(capabilities: &[String]) -> Option<String> {
+    capabilities.iter().find_map(|cap| {
+        cap.strip_prefix("symref=HEAD:refs/heads/")
+            .map(String::from)
+    })
+}
+
+/// `POST $url/git-upload-pack`: wants `head_sha`, then reads back the
+/// packfile the server sends in response.
+fn fetch_pack(url: &str, head_sha: &str) -> anyhow::Result<Vec<u8>> {
+    let mut body = pktline::encode(format!("want {head_sha}\n").as_bytes());
+    body.extend(pktline::flush());
+    body.extend(pktline::encode(b"done\n"));
+
+    let response = ureq::post(&format!("{url}/git-upload-pack"))
+        .set("Content-Type", "application/x-git-upload-pack-request")
+        .send_bytes(&body)
+        .context("POST git-upload-pack")?;
+    let mut raw = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut raw)
+        .context("read git-upload-pack response")?;
+
+    let mut cursor: &[u8] = &raw;
+    let ack = pktline::Reader::new(&mut cursor)
+        .read_packet()
+        .context("read NAK/ACK line")?
+        .context("missing NAK/ACK line")?;
+    anyhow::ensure!(
+        ack.starts_with(b"NAK") || ack.starts_with(b"ACK"),
+        "unexpected upload-pack response: {:?}",
+        String::from_utf8_lossy(&ack)
+    );
+
+    Ok(cursor.to_vec())
+}
+
+/// Checks out the tree of `commit_sha` into `dir`.
+fn checkout(commit_sha: &str, dir: &Path) -> anyhow::Result<()> {
+    let mut commit = Object::read(commit_sha)?;
+    let mut content = Vec::new();
+    commit
+        .reader
+        .read_to_end(&mut content)
+        .context("read commit object")?;
+    let content = String::from_utf8(content).context("commit object isn't UTF-8")?;
+    let tree_sha = content
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("tree "))
+        .context("commit object missing tree header")?;
+    checkout_tree(tree_sha, dir)
+}
+
+fn checkout_tree(tree_sha: &str, dir: &Path) -> anyhow::Result<()> {
+    let mut tree = Object::read(tree_sha)?;
+    anyhow::ensure!(tree.kind == Kind::Tree, "expected a tree object");
+
+    let mut buf = Vec::new();
+    let mut hashbuf = [0u8; 20];
+    loop {
+        buf.clear();
+        let n = tree
+            .reader
+            .read_until(0, &mut buf)
+            .context("read next tree entry")?;
+        if n == 0 {
+            break;
+        }
+        tree.reader
+            .read_exact(&mut hashbuf)
+            .context("read tree entry hash")?;
+
+        let without_ending_null = buf.split_last().context("split last")?.1;
+        let mut iter = without_ending_null.splitn(2, |&b| b == b' ');
+        let mode = iter.next().context("get tree entry mode")?;
+        let name = iter.next().context("get tree entry name")?;
+        let name = std::str::from_utf8(name).context("tree entry name isn't UTF-8")?;
+        let entry_sha = hex::encode(hashbuf);
+        let entry_path = dir.join(name);
+
+        if mode == b"40000" {
+            fs::create_dir_all(&entry_path).context("create directory")?;
+            checkout_tree(&entry_sha, &entry_path)?;
+        } else if mode == b"120000" {
+            let mut blob = Object::read(&entry_sha)?;
+            let mut target = Vec::new();
+            blob.reader
+                .read_to_end(&mut target)
+                .context("read symlink target")?;
+            let target = std::str::from_utf8(&target).context("symlink target isn't UTF-8")?;
+
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(target, &entry_path).context("create symlink")?;
+        } else {
+            let mut blob = Object::read(&entry_sha)?;
+            let mut content = Vec::new();
+            blob.reader
+                .read_to_end(&mut content)
+                .context("read blob object")?;
+            fs::write(&entry_path, &content).context("write file")?;
+
+            #[cfg(unix)]
+            if mode == b"100755" {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&entry_path, fs::Permissions::from_mode(0o755))
+                    .context("set executable bit")?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Parsing for commit objects: header lines (`tree`, `parent`, `author`,
+//! `committer`) plus the free-form message body.
+
+use anyhow::Context;
+
+pub(crate) struct Commit {
+    pub(crate) tree: String,
+    pub(crate) parents: Vec<String>,
+    pub(crate) author: Signature,
+    pub(crate) committer: Signature,
+    pub(crate) message: String,
+}
+
+pub(crate) struct Signature {
+    pub(crate) name: String,
+    pub(crate) email: String,
+    pub(crate) timestamp: String,
+    pub(crate) timezone: String,
+}
+
+impl Commit {
+    pub(crate) fn parse(content: &[u8]) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(content).context("commit object isn't UTF-8")?;
+        let (header, message) = text
+            .split_once("\n\n")
+            .context("commit object missing header/message separator")?;
+
+        let mut tree = None;
+        let mut parents = Vec::new();
+        let mut author = None;
+        let mut committer = None;
+        for line in header.lines() {
+            if let Some(rest) = line.strip_prefix("tree ") {
+                tree = Some(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("parent ") {
+                parents.push(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("author ") {
+                author = Some(Signature::parse(rest)?);
+            } else if let Some(rest) = line.strip_prefix("committer ") {
+                committer = Some(Signature::parse(rest)?);
+            }
+        }
+
+        Ok(Self {
+            tree: tree.context("commit object missing tree header")?,
+            parents,
+            author: author.context("commit object missing author header")?,
+            committer: committer.context("commit object missing committer header")?,
+            message: message.to_string(),
+        })
+    }
+}
+
+impl Signature {
+    /// Parses a `<name> <email> <unixtime> <tz>` signature line.
+    fn parse(line: &str) -> anyhow::Result<Self> {
+        let email_start = line.find('<').context("signature missing '<'")?;
+        let email_end = line.find('>').context("signature missing '>'")?;
+        let name = line[..email_start].trim().to_string();
+        let email = line[email_start + 1..email_end].to_string();
+        let (timestamp, timezone) = line[email_end + 1..]
+            .trim()
+            .split_once(' ')
+            .context("signature missing timestamp/timezone")?;
+        Ok(Self {
+            name,
+            email,
+            timestamp: timestamp.to_string(),
+            timezone: timezone.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Commit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "tree {}", self.tree)?;
+        for parent in &self.parents {
+            writeln!(f, "parent {parent}")?;
+        }
+        writeln!(
+            f,
+            "author {} <{}> {} {}",
+            self.author.name, self.author.email, self.author.timestamp, self.author.timezone
+        )?;
+        writeln!(
+            f,
+            "committer {} <{}> {} {}",
+            self.committer.name,
+            self.committer.email,
+            self.committer.timestamp,
+            self.committer.timezone
+        )?;
+        writeln!(f)?;
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,114 @@
+//! Applies Git's delta encoding (used by `OBJ_OFS_DELTA` / `OBJ_REF_DELTA`
+//! packfile entries) to reconstruct a full object from a base object plus a
+//! compact stream of copy/insert instructions.
+
+use anyhow::Context;
+
+/// Reconstructs the target object by applying `delta` to `base`.
+pub(crate) fn apply(base: &[u8], delta: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut pos = 0;
+    let source_size = read_varint_size(delta, &mut pos)?;
+    anyhow::ensure!(
+        source_size as usize == base.len(),
+        "delta source size {source_size} doesn't match base object size {}",
+        base.len()
+    );
+    let target_size = read_varint_size(delta, &mut pos)?;
+
+    let mut target = Vec::with_capacity(target_size as usize);
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+        if op & 0x80 != 0 {
+            // Copy instruction: the low 4 bits of `op` select which of the 4
+            // little-endian offset bytes are present, the next 3 bits select
+            // which of the 3 little-endian size bytes are present.
+            let mut offset: u32 = 0;
+            for i in 0..4 {
+                if op & (1 << i) != 0 {
+                    offset |= (*delta.get(pos).context("truncated copy offset")? as u32) << (i * 8);
+                    pos += 1;
+                }
+            }
+            let mut size: u32 = 0;
+            for i in 0..3 {
+                if op & (1 << (4 + i)) != 0 {
+                    size |= (*delta.get(pos).context("truncated copy size")? as u32) << (i * 8);
+                    pos += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let (offset, size) = (offset as usize, size as usize);
+            let end = offset
+                .checked_add(size)
+                .context("copy instruction overflows")?;
+            anyhow::ensure!(end <= base.len(), "copy instruction reads past base object");
+            target.extend_from_slice(&base[offset..end]);
+        } else {
+            // Insert instruction: the low 7 bits are the literal length.
+            let len = (op & 0x7f) as usize;
+            anyhow::ensure!(
+                pos + len <= delta.len(),
+                "insert instruction reads past delta"
+            );
+            target.extend_from_slice(&delta[pos..pos + len]);
+            pos += len;
+        }
+    }
+
+    anyhow::ensure!(
+        target.len() as u64 == target_size,
+        "reconstructed object size {} doesn't match delta target size {target_size}",
+        target.len()
+    );
+    Ok(target)
+}
+
+/// Reads one of the delta header's two size varints: 7 bits per byte,
+/// little-endian, continuation bit `0x80`.
+fn read_varint_size(delta: &[u8], pos: &mut usize) -> anyhow::Result<u64> {
+    let mut size = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *delta.get(*pos).context("truncated delta size varint")?;
+        *pos += 1;
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_then_insert() {
+        let base = b"hello world";
+        // source_size=11, target_size=16, copy base[0..11], insert "! bye".
+        let delta = [&[0x0B, 0x10, 0x90, 0x0B, 0x05][..], b"! bye"].concat();
+        let target = apply(base, &delta).unwrap();
+        assert_eq!(target, b"hello world! bye");
+    }
+
+    #[test]
+    fn insert_then_copy() {
+        let base = b"abcdefgh";
+        // source_size=8, target_size=10, insert "XY", copy base[0..8].
+        let delta = [0x08, 0x0A, 0x02, b'X', b'Y', 0x90, 0x08];
+        let target = apply(base, &delta).unwrap();
+        assert_eq!(target, b"XYabcdefgh");
+    }
+
+    #[test]
+    fn copy_rejects_mismatched_source_size() {
+        let base = b"short";
+        let delta = [0x0B, 0x00];
+        assert!(apply(base, &delta).is_err());
+    }
+}
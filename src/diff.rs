@@ -0,0 +1,203 @@
+//! Line-level diffing via Myers' O(ND) shortest-edit-script algorithm,
+//! rendered as a unified diff.
+
+const CONTEXT: usize = 3;
+
+/// One step of the edit script that turns sequence `a` into sequence `b`,
+/// carrying the 0-based line index it refers to (into `a` for `Equal`/
+/// `Delete`, into `b` for `Insert`).
+enum Edit {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes the unified diff between `old` and `new`, line by line.
+pub(crate) fn unified(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let trace = shortest_edit(&a, &b);
+    let offset = ((a.len() + b.len()).max(1)) as isize;
+    let edits = backtrack(&a, &b, &trace, offset);
+
+    // Prefix sums of how many old/new lines have been consumed before edit i,
+    // so a hunk's header can be read off directly from its edit range.
+    let mut old_before = vec![0usize; edits.len() + 1];
+    let mut new_before = vec![0usize; edits.len() + 1];
+    for (i, edit) in edits.iter().enumerate() {
+        old_before[i + 1] =
+            old_before[i] + matches!(edit, Edit::Equal(_) | Edit::Delete(_)) as usize;
+        new_before[i + 1] =
+            new_before[i] + matches!(edit, Edit::Equal(_) | Edit::Insert(_)) as usize;
+    }
+
+    let mut out = String::new();
+    for (lo, hi) in hunk_ranges(&edits) {
+        let old_count = old_before[hi] - old_before[lo];
+        let new_count = new_before[hi] - new_before[lo];
+        let old_start = if old_count > 0 {
+            old_before[lo] + 1
+        } else {
+            old_before[lo]
+        };
+        let new_start = if new_count > 0 {
+            new_before[lo] + 1
+        } else {
+            new_before[lo]
+        };
+
+        out.push_str(&format!(
+            "@@ -{old_start},{old_count} +{new_start},{new_count} @@\n"
+        ));
+        for edit in &edits[lo..hi] {
+            match edit {
+                Edit::Equal(oi) => out.push_str(&format!(" {}\n", a[*oi])),
+                Edit::Delete(oi) => out.push_str(&format!("-{}\n", a[*oi])),
+                Edit::Insert(ni) => out.push_str(&format!("+{}\n", b[*ni])),
+            }
+        }
+    }
+    out
+}
+
+/// Groups the edit script into hunk ranges `[lo, hi)`, each padded with up
+/// to [`CONTEXT`] lines of surrounding equal lines, merging hunks that would
+/// otherwise share context.
+fn hunk_ranges(edits: &[Edit]) -> Vec<(usize, usize)> {
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let Some(&first) = changes.first() else {
+        return Vec::new();
+    };
+
+    let mut clusters = Vec::new();
+    let (mut start, mut end) = (first, first);
+    for &idx in &changes[1..] {
+        if idx - end <= 2 * CONTEXT {
+            end = idx;
+        } else {
+            clusters.push((start, end));
+            start = idx;
+            end = idx;
+        }
+    }
+    clusters.push((start, end));
+
+    clusters
+        .into_iter()
+        .map(|(start, end)| {
+            let lo = start.saturating_sub(CONTEXT);
+            let hi = (end + CONTEXT + 1).min(edits.len());
+            (lo, hi)
+        })
+        .collect()
+}
+
+/// Runs Myers' algorithm, returning the `v` array (the furthest-reaching
+/// x on each diagonal `k`) recorded at every edit distance `d`, so the path
+/// can be recovered by backtracking.
+fn shortest_edit(a: &[&str], b: &[&str]) -> Vec<Vec<isize>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (n + m).max(1);
+    let offset = max;
+
+    let mut v = vec![0isize; (2 * max + 1) as usize];
+    let mut trace = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                return trace;
+            }
+            k += 2;
+        }
+    }
+    trace
+}
+
+/// Walks the recorded `trace` backwards from `(a.len(), b.len())` to `(0,
+/// 0)`, turning each step of the shortest edit path into an `Edit`.
+fn backtrack(a: &[&str], b: &[&str], trace: &[Vec<isize>], offset: isize) -> Vec<Edit> {
+    let mut x = a.len() as isize;
+    let mut y = b.len() as isize;
+    let mut edits = Vec::new();
+
+    for d in (0..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let idx = |k: isize| (k + offset) as usize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[idx(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                edits.push(Edit::Insert(y as usize));
+            } else {
+                x -= 1;
+                edits.push(Edit::Delete(x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_sequences_produce_no_hunks() {
+        assert_eq!(unified("a\nb\nc\n", "a\nb\nc\n"), "");
+    }
+
+    #[test]
+    fn single_line_replacement() {
+        assert_eq!(
+            unified("a\nb\nc\n", "a\nx\nc\n"),
+            "@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n"
+        );
+    }
+
+    #[test]
+    fn trailing_insert() {
+        assert_eq!(
+            unified("1\n2\n3\n", "1\n2\n3\n4\n"),
+            "@@ -1,3 +1,4 @@\n 1\n 2\n 3\n+4\n"
+        );
+    }
+}
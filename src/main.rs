@@ -9,6 +9,15 @@ use std::io::BufReader;
 use std::path::Path;
 use std::path::PathBuf;
 
+mod clone;
+mod commit;
+mod delta;
+mod diff;
+mod pack_index;
+mod pack_objects;
+mod packfile;
+mod pktline;
+
 #[derive(Parser, Debug)]
 struct Args {
     #[command(subcommand)]
@@ -58,6 +67,33 @@ enum Command {
         #[arg(short)]
         message: String,
     },
+
+    Clone {
+        /// The (possibly remote) repository to clone from.
+        url: String,
+
+        /// The name of a new directory to clone into.
+        dir: String,
+    },
+
+    Pack {
+        /// The commit to start walking from; every blob, tree, and commit it
+        /// can reach is included in the packfile.
+        commit: String,
+    },
+
+    Diff {
+        /// The id of the blob to diff from.
+        old: String,
+
+        /// The id of the blob to diff to.
+        new: String,
+    },
+
+    Log {
+        /// The commit to start walking from.
+        commit: String,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -83,7 +119,19 @@ fn main() -> anyhow::Result<()> {
                     std::io::copy(&mut object.reader, &mut handle)
                         .context("write blob to stdout")?;
                 }
-                _ => todo!(),
+                Kind::Tree => {
+                    let stdout = io::stdout();
+                    let mut handle = stdout.lock();
+                    print_tree_entries(&mut object, &mut handle, false)?;
+                }
+                Kind::Commit => {
+                    let mut content = Vec::new();
+                    object
+                        .reader
+                        .read_to_end(&mut content)
+                        .context("read commit object")?;
+                    print!("{}", commit::Commit::parse(&content)?);
+                }
             }
         }
         Command::HashObject { write, file } => {
@@ -101,35 +149,7 @@ fn main() -> anyhow::Result<()> {
                 Kind::Tree => {
                     let stdout = io::stdout();
                     let mut handle = stdout.lock();
-                    let mut buf = Vec::new();
-                    let mut hashbuf = [0; 20];
-                    loop {
-                        buf.clear();
-                        let n = object
-                            .reader
-                            .read_until(0, &mut buf)
-                            .context("read next tree entry")?;
-                        if n == 0 {
-                            break;
-                        }
-                        object
-                            .reader
-                            .read_exact(&mut hashbuf)
-                            .context("read tree entry hash")?;
-
-                        let without_ending_null = buf.split_last().context("split last")?.1;
-                        let mut iter = without_ending_null.splitn(2, |&b| b == b' ');
-                        let _mode = iter.next().context("get tree entry mode")?;
-                        let name = iter.next().context("get tree entry name")?;
-
-                        match name_only {
-                            true => {
-                                handle.write_all(name).context("write name to console")?;
-                                write!(handle, "\n").context("write new line to console")?;
-                            }
-                            false => todo!(),
-                        }
-                    }
+                    print_tree_entries(&mut object, &mut handle, name_only)?;
                 }
                 _ => todo!(),
             }
@@ -161,17 +181,130 @@ fn main() -> anyhow::Result<()> {
             let hash = Object::write(Kind::Commit, &content)?;
             println!("{}", hex::encode(hash));
         }
+        Command::Clone { url, dir } => {
+            clone::run(&url, &dir)?;
+        }
+        Command::Pack { commit } => {
+            let objects = pack_objects::collect_reachable(&commit)?;
+            let pack = packfile::pack(&objects)?;
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            handle
+                .write_all(&pack)
+                .context("write packfile to stdout")?;
+        }
+        Command::Diff { old, new } => {
+            let mut old_content = Vec::new();
+            Object::read(&old)?
+                .reader
+                .read_to_end(&mut old_content)
+                .context("read old blob")?;
+            let old_text = String::from_utf8(old_content).context("old blob isn't UTF-8")?;
+
+            let mut new_content = Vec::new();
+            Object::read(&new)?
+                .reader
+                .read_to_end(&mut new_content)
+                .context("read new blob")?;
+            let new_text = String::from_utf8(new_content).context("new blob isn't UTF-8")?;
+
+            print!("{}", diff::unified(&old_text, &new_text));
+        }
+        Command::Log { commit } => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+
+            let mut next = Some(commit);
+            while let Some(oid) = next {
+                let mut object = Object::read(&oid)?;
+                anyhow::ensure!(object.kind == Kind::Commit, "{oid} is not a commit");
+                let mut content = Vec::new();
+                object
+                    .reader
+                    .read_to_end(&mut content)
+                    .context("read commit object")?;
+                let parsed = commit::Commit::parse(&content)?;
+
+                writeln!(handle, "commit {oid}").context("write log entry")?;
+                writeln!(
+                    handle,
+                    "Author: {} <{}>",
+                    parsed.author.name, parsed.author.email
+                )
+                .context("write log entry")?;
+                writeln!(handle).context("write log entry")?;
+                for line in parsed.message.lines() {
+                    writeln!(handle, "    {line}").context("write log entry")?;
+                }
+                writeln!(handle).context("write log entry")?;
+
+                next = parsed.parents.into_iter().next();
+            }
+        }
     }
     Ok(())
 }
 
-fn encode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+/// Prints a tree object's entries, one per line: just the name when
+/// `name_only`, otherwise the long form `<mode> <type> <hash>\t<name>`.
+fn print_tree_entries(
+    object: &mut Object<Box<dyn BufRead>>,
+    handle: &mut impl Write,
+    name_only: bool,
+) -> anyhow::Result<()> {
+    let mut buf = Vec::new();
+    let mut hashbuf = [0; 20];
+    loop {
+        buf.clear();
+        let n = object
+            .reader
+            .read_until(0, &mut buf)
+            .context("read next tree entry")?;
+        if n == 0 {
+            break;
+        }
+        object
+            .reader
+            .read_exact(&mut hashbuf)
+            .context("read tree entry hash")?;
+
+        let without_ending_null = buf.split_last().context("split last")?.1;
+        let mut iter = without_ending_null.splitn(2, |&b| b == b' ');
+        let mode = iter.next().context("get tree entry mode")?;
+        let name = iter.next().context("get tree entry name")?;
+
+        if name_only {
+            handle.write_all(name).context("write name to console")?;
+            writeln!(handle).context("write new line to console")?;
+        } else {
+            let mode_str = std::str::from_utf8(mode).context("tree entry mode isn't UTF-8")?;
+            let kind = match mode_str {
+                "40000" => "tree",
+                "160000" => "commit",
+                _ => "blob",
+            };
+            writeln!(
+                handle,
+                "{:0>6} {} {}\t{}",
+                mode_str,
+                kind,
+                hex::encode(hashbuf),
+                std::str::from_utf8(name).context("tree entry name isn't UTF-8")?
+            )
+            .context("write tree entry")?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn encode(bytes: &[u8]) -> io::Result<Vec<u8>> {
     let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
     e.write_all(bytes).unwrap();
     e.finish()
 }
 
-enum Kind {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
     Blob,
     Tree,
     Commit,
@@ -198,50 +331,56 @@ impl std::fmt::Display for Kind {
     }
 }
 
-struct Object<R> {
-    kind: Kind,
+pub(crate) struct Object<R> {
+    pub(crate) kind: Kind,
     #[allow(dead_code)]
-    expected_size: u64,
-    reader: R,
+    pub(crate) expected_size: u64,
+    pub(crate) reader: R,
 }
 
 impl Object<()> {
-    fn read(hash: &str) -> anyhow::Result<Object<impl BufRead>> {
+    /// Reads an object, either as a loose file in `.git/objects/<2>/<38>`,
+    /// or, failing that, out of a packfile via its `.idx` index.
+    pub(crate) fn read(hash: &str) -> anyhow::Result<Object<Box<dyn BufRead>>> {
         let (folder, filename) = hash.split_at(2);
         let file_path = format!(".git/objects/{}/{}", folder, filename);
-        let f = fs::File::open(&file_path).context(format!("open file: {}", file_path))?;
-        let z = ZlibDecoder::new(f);
-        let mut reader = BufReader::new(z);
-        let mut buf = Vec::new();
-        reader
-            .read_until(0, &mut buf)
-            .context("read header from .git/objects")?;
-        let without_ending_null = buf.split_last().context("split last")?.1;
-        let header = String::from_utf8(without_ending_null.to_vec())
-            .context("object header isn't valid UTF-8")?;
-        let (kind, size) = header.split_once(' ').context(format!(
-            "object header isn't `<kink> <size>\0`: {:?}",
-            header
-        ))?;
-        let size = size
-            .parse::<u64>()
-            .context(format!("object header has invalid size: {}", size))?;
+        if let Ok(f) = fs::File::open(&file_path) {
+            let z = ZlibDecoder::new(f);
+            let mut reader = BufReader::new(z);
+            let mut buf = Vec::new();
+            reader
+                .read_until(0, &mut buf)
+                .context("read header from .git/objects")?;
+            let without_ending_null = buf.split_last().context("split last")?.1;
+            let header = String::from_utf8(without_ending_null.to_vec())
+                .context("object header isn't valid UTF-8")?;
+            let (kind, size) = header.split_once(' ').context(format!(
+                "object header isn't `<kink> <size>\0`: {:?}",
+                header
+            ))?;
+            let size = size
+                .parse::<u64>()
+                .context(format!("object header has invalid size: {}", size))?;
+            return Ok(Object {
+                kind: Kind::from(kind)?,
+                expected_size: size,
+                reader: Box::new(reader.take(size)),
+            });
+        }
+
+        let (kind, content) = pack_index::read(hash)
+            .context("search packfile indexes")?
+            .with_context(|| format!("object not found: {hash}"))?;
         Ok(Object {
-            kind: Kind::from(kind)?,
-            expected_size: size,
-            reader: reader.take(size),
+            kind,
+            expected_size: content.len() as u64,
+            reader: Box::new(BufReader::new(io::Cursor::new(content))),
         })
     }
 
-    fn write(kind: Kind, content: &[u8]) -> anyhow::Result<Vec<u8>> {
-        let mut git_object_formatted_content = format!("{} {}\0", kind, content.len()).into_bytes();
-        git_object_formatted_content.extend(content);
-
-        // do hash
-        let mut hasher = Sha1::new();
-        hasher.update(&git_object_formatted_content[..]);
-        let hash = hasher.finalize();
-        let hash_hex = hex::encode(hash);
+    pub(crate) fn write(kind: Kind, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let (hash, git_object_formatted_content) = hash_object(kind, content);
+        let hash_hex = hex::encode(&hash);
 
         // write git object
         let target_dir = format!(".git/objects/{}", &hash_hex[..2]);
@@ -251,10 +390,23 @@ impl Object<()> {
             encode(&git_object_formatted_content[..])?,
         )?;
 
-        Ok(hash.to_vec())
+        Ok(hash)
     }
 }
 
+/// Computes the object hash (and the `<kind> <size>\0<content>` bytes that
+/// hash covers) the same way `Object::write` does, without touching disk.
+pub(crate) fn hash_object(kind: Kind, content: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut git_object_formatted_content = format!("{} {}\0", kind, content.len()).into_bytes();
+    git_object_formatted_content.extend(content);
+
+    let mut hasher = Sha1::new();
+    hasher.update(&git_object_formatted_content[..]);
+    let hash = hasher.finalize().to_vec();
+
+    (hash, git_object_formatted_content)
+}
+
 fn write_tree(path: &PathBuf) -> anyhow::Result<Option<Vec<u8>>> {
     anyhow::ensure!(path.is_dir(), "write to tree in path: {:?}", path);
 
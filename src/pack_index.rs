@@ -0,0 +1,183 @@
+//! Reads an object out of a packfile via its `.idx` index, as a fallback for
+//! when `Object::read` can't find it as a loose file (e.g. after a `clone`).
+
+use anyhow::Context;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::packfile::{
+    inflate, read_entry_header, read_ofs_delta_offset, to_object_kind, OBJ_BLOB, OBJ_COMMIT,
+    OBJ_OFS_DELTA, OBJ_REF_DELTA, OBJ_TREE,
+};
+use crate::{delta, Kind, Object};
+
+/// Looks up `hash` (hex-encoded) across every `.idx` in `.git/objects/pack`,
+/// returning its kind and fully-resolved content if found.
+pub(crate) fn read(hash: &str) -> anyhow::Result<Option<(Kind, Vec<u8>)>> {
+    let pack_dir = Path::new(".git/objects/pack");
+    if !pack_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let target = hex_to_sha(hash)?;
+    for entry in fs::read_dir(pack_dir).context("read .git/objects/pack")? {
+        let idx_path = entry.context("read pack directory entry")?.path();
+        if idx_path.extension().and_then(|e| e.to_str()) != Some("idx") {
+            continue;
+        }
+
+        let index = Index::parse(&fs::read(&idx_path).context("read idx file")?)?;
+        let Some(offset) = index.find(&target) else {
+            continue;
+        };
+
+        let pack = fs::read(idx_path.with_extension("pack")).context("read pack file")?;
+        return Ok(Some(read_at_offset(&pack, offset)?));
+    }
+    Ok(None)
+}
+
+/// A parsed v2 `.idx` file: 4-byte magic, version, a 256-entry fanout table,
+/// the sorted 20-byte SHA-1 table, a CRC32 table, and an offset table (with
+/// an 8-byte large-offset table used when the high bit of an offset is set).
+struct Index {
+    fanout: [u32; 256],
+    shas: Vec<[u8; 20]>,
+    offsets: Vec<u32>,
+    large_offsets: Vec<u64>,
+}
+
+impl Index {
+    fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        anyhow::ensure!(data.len() >= 8, "pack idx too short");
+        anyhow::ensure!(&data[0..4] == b"\xfftOc", "missing pack idx magic");
+        let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+        anyhow::ensure!(version == 2, "unsupported pack idx version: {version}");
+
+        let mut pos = 8;
+        let mut fanout = [0u32; 256];
+        for slot in &mut fanout {
+            *slot = read_u32(data, &mut pos)?;
+        }
+        let count = fanout[255] as usize;
+
+        let mut shas = Vec::with_capacity(count);
+        for _ in 0..count {
+            let sha = data
+                .get(pos..pos + 20)
+                .context("truncated pack idx sha table")?;
+            shas.push(sha.try_into().unwrap());
+            pos += 20;
+        }
+
+        pos += count * 4; // CRC32 table, unused.
+
+        let mut offsets = Vec::with_capacity(count);
+        for _ in 0..count {
+            offsets.push(read_u32(data, &mut pos)?);
+        }
+
+        let large_count = offsets.iter().filter(|&&o| o & 0x8000_0000 != 0).count();
+        let mut large_offsets = Vec::with_capacity(large_count);
+        for _ in 0..large_count {
+            large_offsets.push(read_u64(data, &mut pos)?);
+        }
+
+        Ok(Self {
+            fanout,
+            shas,
+            offsets,
+            large_offsets,
+        })
+    }
+
+    /// Binary-searches the sha table (bounded by the fanout table) and
+    /// returns the matching object's offset into the `.pack` file, if any.
+    fn find(&self, target: &[u8; 20]) -> Option<usize> {
+        let first_byte = target[0] as usize;
+        let lo = if first_byte == 0 {
+            0
+        } else {
+            self.fanout[first_byte - 1] as usize
+        };
+        let hi = self.fanout[first_byte] as usize;
+
+        let index = lo + self.shas[lo..hi].binary_search(target).ok()?;
+        let offset = self.offsets[index];
+        Some(if offset & 0x8000_0000 != 0 {
+            self.large_offsets[(offset & 0x7fff_ffff) as usize] as usize
+        } else {
+            offset as usize
+        })
+    }
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> anyhow::Result<u32> {
+    let value = data
+        .get(*pos..*pos + 4)
+        .context("truncated pack idx")?
+        .try_into()
+        .unwrap();
+    *pos += 4;
+    Ok(u32::from_be_bytes(value))
+}
+
+fn read_u64(data: &[u8], pos: &mut usize) -> anyhow::Result<u64> {
+    let value = data
+        .get(*pos..*pos + 8)
+        .context("truncated pack idx")?
+        .try_into()
+        .unwrap();
+    *pos += 8;
+    Ok(u64::from_be_bytes(value))
+}
+
+fn hex_to_sha(hash: &str) -> anyhow::Result<[u8; 20]> {
+    let bytes = hex::decode(hash).context("object id isn't hex")?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("object id isn't 20 bytes"))
+}
+
+/// Decodes the object at `offset` into `pack`, resolving its delta chain (if
+/// any). `OBJ_OFS_DELTA` bases live at another offset in this same pack;
+/// `OBJ_REF_DELTA` bases are looked up the normal way, which lets them live
+/// as a loose file or in any pack.
+fn read_at_offset(pack: &[u8], offset: usize) -> anyhow::Result<(Kind, Vec<u8>)> {
+    let (kind, size, header_len) = read_entry_header(&pack[offset..])?;
+    let body_start = offset + header_len;
+
+    match kind {
+        OBJ_COMMIT | OBJ_TREE | OBJ_BLOB => {
+            let (content, _) = inflate(&pack[body_start..], size)?;
+            Ok((to_object_kind(kind)?, content))
+        }
+        OBJ_OFS_DELTA => {
+            let (back_offset, used) = read_ofs_delta_offset(&pack[body_start..])?;
+            let delta_start = body_start + used;
+            let base_offset = offset
+                .checked_sub(back_offset as usize)
+                .context("ofs-delta offset points before the start of the pack")?;
+            let (delta_bytes, _) = inflate(&pack[delta_start..], size)?;
+            let (base_kind, base_content) = read_at_offset(pack, base_offset)?;
+            Ok((base_kind, delta::apply(&base_content, &delta_bytes)?))
+        }
+        OBJ_REF_DELTA => {
+            let base_sha = hex::encode(
+                pack.get(body_start..body_start + 20)
+                    .context("truncated ref-delta base")?,
+            );
+            let delta_start = body_start + 20;
+            let (delta_bytes, _) = inflate(&pack[delta_start..], size)?;
+
+            let mut base = Object::read(&base_sha).context("read ref-delta base object")?;
+            let mut base_content = Vec::new();
+            base.reader
+                .read_to_end(&mut base_content)
+                .context("read ref-delta base content")?;
+            Ok((base.kind, delta::apply(&base_content, &delta_bytes)?))
+        }
+        other => anyhow::bail!("unknown packfile object type: {other}"),
+    }
+}
@@ -0,0 +1,75 @@
+//! Walks the object graph reachable from a commit, collecting everything
+//! needed to pack it into a packfile (e.g. to serve an `upload-pack`
+//! request).
+
+use anyhow::Context;
+use std::collections::HashSet;
+use std::io::prelude::*;
+
+use crate::{Kind, Object};
+
+/// Collects every blob, tree, and commit object reachable from `commit_sha`
+/// by walking its tree and parent chain.
+pub(crate) fn collect_reachable(commit_sha: &str) -> anyhow::Result<Vec<(Kind, Vec<u8>)>> {
+    let mut seen = HashSet::new();
+    let mut objects = Vec::new();
+    let mut pending = vec![commit_sha.to_string()];
+
+    while let Some(sha) = pending.pop() {
+        if !seen.insert(sha.clone()) {
+            continue;
+        }
+
+        let mut object = Object::read(&sha).context(format!("read object {sha}"))?;
+        let mut content = Vec::new();
+        object
+            .reader
+            .read_to_end(&mut content)
+            .context("read object content")?;
+
+        match object.kind {
+            Kind::Commit => pending.extend(commit_parents_and_tree(&content)?),
+            Kind::Tree => pending.extend(tree_entry_hashes(&content)?),
+            Kind::Blob => {}
+        }
+
+        objects.push((object.kind, content));
+    }
+
+    Ok(objects)
+}
+
+/// Extracts the `tree` and `parent` hashes out of a commit object's header.
+fn commit_parents_and_tree(content: &[u8]) -> anyhow::Result<Vec<String>> {
+    let text = std::str::from_utf8(content).context("commit object isn't UTF-8")?;
+    let mut hashes = Vec::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            break;
+        }
+        if let Some(hash) = line
+            .strip_prefix("tree ")
+            .or_else(|| line.strip_prefix("parent "))
+        {
+            hashes.push(hash.to_string());
+        }
+    }
+    Ok(hashes)
+}
+
+/// Extracts the child object hashes out of a tree object's raw content.
+fn tree_entry_hashes(content: &[u8]) -> anyhow::Result<Vec<String>> {
+    let mut hashes = Vec::new();
+    let mut rest = content;
+    while !rest.is_empty() {
+        let nul = rest
+            .iter()
+            .position(|&b| b == 0)
+            .context("truncated tree entry")?;
+        rest = &rest[nul + 1..];
+        let hash = rest.get(..20).context("truncated tree entry hash")?;
+        hashes.push(hex::encode(hash));
+        rest = &rest[20..];
+    }
+    Ok(hashes)
+}
@@ -0,0 +1,273 @@
+//! Parsing for Git packfiles, as produced by `git-upload-pack`.
+
+use anyhow::Context;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::Read;
+
+use crate::{delta, encode, hash_object, Kind};
+
+pub(crate) const OBJ_COMMIT: u8 = 1;
+pub(crate) const OBJ_TREE: u8 = 2;
+pub(crate) const OBJ_BLOB: u8 = 3;
+pub(crate) const OBJ_TAG: u8 = 4;
+pub(crate) const OBJ_OFS_DELTA: u8 = 6;
+pub(crate) const OBJ_REF_DELTA: u8 = 7;
+
+/// A packfile entry before its delta chain (if any) has been resolved.
+enum Entry {
+    Base { kind: Kind, content: Vec<u8> },
+    OfsDelta { base_offset: usize, delta: Vec<u8> },
+    RefDelta { base_sha: String, delta: Vec<u8> },
+}
+
+/// Reads a packfile and returns every object it contains as a resolved
+/// `(Kind, content)` pair, in the order they appear in the pack.
+pub(crate) fn unpack(data: &[u8]) -> anyhow::Result<Vec<(Kind, Vec<u8>)>> {
+    anyhow::ensure!(data.len() >= 12, "packfile too short");
+    anyhow::ensure!(&data[0..4] == b"PACK", "missing PACK magic");
+    let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+    anyhow::ensure!(version == 2, "unsupported packfile version: {version}");
+    let count = u32::from_be_bytes(data[8..12].try_into().unwrap()) as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    let mut offset_to_index = HashMap::new();
+    let mut offset = 12usize;
+    for index in 0..count {
+        let entry_start = offset;
+        offset_to_index.insert(entry_start, index);
+
+        let (kind, size, header_len) = read_entry_header(&data[offset..])?;
+        offset += header_len;
+
+        match kind {
+            OBJ_COMMIT | OBJ_TREE | OBJ_BLOB => {
+                let (content, used) = inflate(&data[offset..], size)?;
+                offset += used;
+                entries.push(Entry::Base {
+                    kind: to_object_kind(kind)?,
+                    content,
+                });
+            }
+            OBJ_TAG => anyhow::bail!("annotated tag objects are not supported"),
+            OBJ_OFS_DELTA => {
+                let (back_offset, used) = read_ofs_delta_offset(&data[offset..])?;
+                offset += used;
+                let base_offset = entry_start
+                    .checked_sub(back_offset as usize)
+                    .context("ofs-delta offset points before the start of the pack")?;
+                let (delta_bytes, used) = inflate(&data[offset..], size)?;
+                offset += used;
+                entries.push(Entry::OfsDelta {
+                    base_offset,
+                    delta: delta_bytes,
+                });
+            }
+            OBJ_REF_DELTA => {
+                let base_sha = hex::encode(
+                    data.get(offset..offset + 20)
+                        .context("truncated ref-delta base")?,
+                );
+                offset += 20;
+                let (delta_bytes, used) = inflate(&data[offset..], size)?;
+                offset += used;
+                entries.push(Entry::RefDelta {
+                    base_sha,
+                    delta: delta_bytes,
+                });
+            }
+            other => anyhow::bail!("unknown packfile object type: {other}"),
+        }
+    }
+
+    let mut resolved: Vec<Option<(Kind, Vec<u8>)>> = entries.iter().map(|_| None).collect();
+    let mut sha_to_index = HashMap::new();
+    for index in 0..entries.len() {
+        resolve(
+            index,
+            &entries,
+            &offset_to_index,
+            &mut resolved,
+            &mut sha_to_index,
+        )?;
+    }
+    Ok(resolved.into_iter().map(|r| r.unwrap()).collect())
+}
+
+/// Resolves entry `index` to its final `(Kind, content)`, recursively
+/// resolving and applying any delta chain, and memoizes the result.
+fn resolve(
+    index: usize,
+    entries: &[Entry],
+    offset_to_index: &HashMap<usize, usize>,
+    resolved: &mut Vec<Option<(Kind, Vec<u8>)>>,
+    sha_to_index: &mut HashMap<String, usize>,
+) -> anyhow::Result<(Kind, Vec<u8>)> {
+    if let Some(object) = &resolved[index] {
+        return Ok(object.clone());
+    }
+
+    let object = match &entries[index] {
+        Entry::Base { kind, content } => (*kind, content.clone()),
+        Entry::OfsDelta {
+            base_offset,
+            delta: delta_bytes,
+        } => {
+            let base_index = *offset_to_index
+                .get(base_offset)
+                .context("ofs-delta base offset doesn't point at an object")?;
+            let (kind, base_content) =
+                resolve(base_index, entries, offset_to_index, resolved, sha_to_index)?;
+            (kind, delta::apply(&base_content, delta_bytes)?)
+        }
+        Entry::RefDelta {
+            base_sha,
+            delta: delta_bytes,
+        } => {
+            let (kind, base_content) =
+                resolve_by_sha(base_sha, entries, offset_to_index, resolved, sha_to_index)?;
+            (kind, delta::apply(&base_content, delta_bytes)?)
+        }
+    };
+
+    let (hash, _) = hash_object(object.0, &object.1);
+    sha_to_index.entry(hex::encode(hash)).or_insert(index);
+    resolved[index] = Some(object.clone());
+    Ok(object)
+}
+
+/// Resolves the entry whose final object hash is `sha`, searching (and
+/// resolving, as needed) the whole pack since a ref-delta's base may appear
+/// anywhere in the stream.
+fn resolve_by_sha(
+    sha: &str,
+    entries: &[Entry],
+    offset_to_index: &HashMap<usize, usize>,
+    resolved: &mut Vec<Option<(Kind, Vec<u8>)>>,
+    sha_to_index: &mut HashMap<String, usize>,
+) -> anyhow::Result<(Kind, Vec<u8>)> {
+    if let Some(&index) = sha_to_index.get(sha) {
+        return resolve(index, entries, offset_to_index, resolved, sha_to_index);
+    }
+    for index in 0..entries.len() {
+        if resolved[index].is_some() {
+            continue;
+        }
+        let object = resolve(index, entries, offset_to_index, resolved, sha_to_index)?;
+        if sha_to_index.get(sha) == Some(&index) {
+            return Ok(object);
+        }
+    }
+    anyhow::bail!("ref-delta base {sha} not found in packfile")
+}
+
+/// Parses the variable-length `(type, size)` header that precedes every
+/// packfile entry: the low nibble of the first byte holds 4 size bits (3 type
+/// bits sit above them), and continuation bit `0x80` means another 7 size
+/// bits follow, little-endian. Returns the type, the uncompressed size, and
+/// how many bytes the header itself took up.
+pub(crate) fn read_entry_header(data: &[u8]) -> anyhow::Result<(u8, u64, usize)> {
+    let first = *data.first().context("truncated object header")?;
+    let kind = (first >> 4) & 0x07;
+    let mut size = (first & 0x0f) as u64;
+    let mut shift = 4;
+    let mut i = 1;
+    let mut byte = first;
+    while byte & 0x80 != 0 {
+        byte = *data.get(i).context("truncated object header")?;
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        i += 1;
+    }
+    Ok((kind, size, i))
+}
+
+/// Parses an ofs-delta's back-offset: big-endian 7-bit groups, continuation
+/// bit `0x80`, with a `+1` carry added before each continuation group.
+pub(crate) fn read_ofs_delta_offset(data: &[u8]) -> anyhow::Result<(u64, usize)> {
+    let mut i = 0;
+    let mut byte = *data.get(i).context("truncated ofs-delta offset")?;
+    i += 1;
+    let mut value = (byte & 0x7f) as u64;
+    while byte & 0x80 != 0 {
+        byte = *data.get(i).context("truncated ofs-delta offset")?;
+        i += 1;
+        value = ((value + 1) << 7) | (byte & 0x7f) as u64;
+    }
+    Ok((value, i))
+}
+
+pub(crate) fn to_object_kind(kind: u8) -> anyhow::Result<Kind> {
+    match kind {
+        OBJ_COMMIT => Ok(Kind::Commit),
+        OBJ_TREE => Ok(Kind::Tree),
+        OBJ_BLOB => Ok(Kind::Blob),
+        _ => anyhow::bail!("unsupported packfile object kind: {kind}"),
+    }
+}
+
+/// Serializes `objects` into a valid packfile: `PACK` magic, version `2`, a
+/// big-endian object count, then each object as a variable-length type+size
+/// header followed by its zlib-compressed content, closed with a SHA-1
+/// trailer over the whole stream.
+pub(crate) fn pack(objects: &[(Kind, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"PACK");
+    out.extend_from_slice(&2u32.to_be_bytes());
+    out.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+
+    for (kind, content) in objects {
+        out.extend(write_entry_header(
+            to_packfile_kind(*kind),
+            content.len() as u64,
+        ));
+        out.extend(encode(content).context("compress packfile object")?);
+    }
+
+    out.extend_from_slice(&Sha1::digest(&out));
+    Ok(out)
+}
+
+fn to_packfile_kind(kind: Kind) -> u8 {
+    match kind {
+        Kind::Commit => OBJ_COMMIT,
+        Kind::Tree => OBJ_TREE,
+        Kind::Blob => OBJ_BLOB,
+    }
+}
+
+/// Writes the variable-length `(type, size)` header described in
+/// [`read_entry_header`]'s doc comment, in the opposite direction.
+fn write_entry_header(kind: u8, size: u64) -> Vec<u8> {
+    let mut bytes = vec![(kind << 4) | (size & 0x0f) as u8];
+    let mut size = size >> 4;
+    if size > 0 {
+        bytes[0] |= 0x80;
+    }
+    while size > 0 {
+        let mut byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size > 0 {
+            byte |= 0x80;
+        }
+        bytes.push(byte);
+    }
+    bytes
+}
+
+/// Inflates the zlib stream starting at `body`, returning the decompressed
+/// bytes plus the number of input bytes the stream consumed (the DEFLATE end
+/// marker tells us exactly where the next object starts).
+pub(crate) fn inflate(body: &[u8], expected_size: u64) -> anyhow::Result<(Vec<u8>, usize)> {
+    let mut decoder = flate2::read::ZlibDecoder::new(body);
+    let mut content = Vec::with_capacity(expected_size as usize);
+    decoder
+        .read_to_end(&mut content)
+        .context("inflate packfile object")?;
+    anyhow::ensure!(
+        content.len() as u64 == expected_size,
+        "object size mismatch: expected {expected_size}, got {}",
+        content.len()
+    );
+    Ok((content, decoder.total_in() as usize))
+}
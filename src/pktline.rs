@@ -0,0 +1,53 @@
+//! Minimal pkt-line framing, as used by Git's smart HTTP transport.
+//!
+//! Each pkt-line is a 4-byte ASCII hex length (including those 4 bytes)
+//! followed by that many payload bytes. A length of `0000` is a flush packet
+//! and carries no payload.
+
+use std::io::{self, Read};
+
+/// Encodes `payload` as a single pkt-line.
+pub(crate) fn encode(payload: &[u8]) -> Vec<u8> {
+    let mut out = format!("{:04x}", payload.len() + 4).into_bytes();
+    out.extend_from_slice(payload);
+    out
+}
+
+/// The flush packet (`0000`) that terminates a list of pkt-lines.
+pub(crate) fn flush() -> Vec<u8> {
+    b"0000".to_vec()
+}
+
+/// Reads pkt-lines off of an underlying byte stream.
+pub(crate) struct Reader<R> {
+    inner: R,
+}
+
+impl<R: Read> Reader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Reads the next pkt-line payload, or `None` on a flush packet (or EOF).
+    pub(crate) fn read_packet(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut len_buf = [0u8; 4];
+        if let Err(e) = self.inner.read_exact(&mut len_buf) {
+            return if e.kind() == io::ErrorKind::UnexpectedEof {
+                Ok(None)
+            } else {
+                Err(e)
+            };
+        }
+        let len_str = std::str::from_utf8(&len_buf).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "pkt-line length isn't ascii")
+        })?;
+        let len = usize::from_str_radix(len_str, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "pkt-line length isn't hex"))?;
+        if len == 0 {
+            return Ok(None);
+        }
+        let mut payload = vec![0u8; len - 4];
+        self.inner.read_exact(&mut payload)?;
+        Ok(Some(payload))
+    }
+}